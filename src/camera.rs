@@ -0,0 +1,200 @@
+//! A minimal 2D camera (pan + zoom), following the learn-wgpu camera
+//! pattern: a `Pod` uniform holding a view-projection matrix, uploaded to a
+//! uniform buffer and bound at `@group(0) @binding(0)` in the shader.
+
+use bytemuck::{Pod, Zeroable};
+use wgpu::util::DeviceExt;
+
+/// Pans and scales the maze, which otherwise spans clip space `[-1, 1]`.
+pub struct Camera {
+    pub pan: [f32; 2],
+    pub zoom: f32,
+    /// `window_width / window_height`, so that non-square windows don't
+    /// stretch the maze's already square-corrected geometry.
+    pub aspect: f32,
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self {
+            pan: [0.0, 0.0],
+            zoom: 1.0,
+            aspect: 1.0,
+        }
+    }
+}
+
+impl Camera {
+    /// Per-axis scale that compensates for a non-square window: the axis
+    /// that's "too long" gets shrunk so a unit square in world space still
+    /// renders as a square on screen.
+    fn axis_scale(&self) -> (f32, f32) {
+        if self.aspect > 1.0 {
+            (1.0 / self.aspect, 1.0)
+        } else {
+            (1.0, self.aspect)
+        }
+    }
+
+    /// `world = clip / (zoom * axis_scale) + pan`, so `view_proj` is its
+    /// inverse: `clip = (world - pan) * zoom * axis_scale`.
+    pub fn build_view_projection_matrix(&self) -> [[f32; 4]; 4] {
+        let [px, py] = self.pan;
+        let z = self.zoom;
+        let (sx, sy) = self.axis_scale();
+        [
+            [z * sx, 0.0, 0.0, 0.0],
+            [0.0, z * sy, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [-px * z * sx, -py * z * sy, 0.0, 1.0],
+        ]
+    }
+
+    /// Maps a point in clip space (e.g. a cursor position already converted
+    /// to NDC) back into the world space this camera views.
+    pub fn clip_to_world(&self, clip: [f32; 2]) -> [f32; 2] {
+        let (sx, sy) = self.axis_scale();
+        [
+            clip[0] / (self.zoom * sx) + self.pan[0],
+            clip[1] / (self.zoom * sy) + self.pan[1],
+        ]
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct CameraUniform {
+    view_proj: [[f32; 4]; 4],
+}
+
+impl CameraUniform {
+    pub fn new(camera: &Camera) -> Self {
+        Self {
+            view_proj: camera.build_view_projection_matrix(),
+        }
+    }
+
+    pub fn update(&mut self, camera: &Camera) {
+        self.view_proj = camera.build_view_projection_matrix();
+    }
+}
+
+/// The GPU-side resources backing a [`CameraUniform`]: the buffer the
+/// shader reads from, and the bind group (plus its layout) wiring it into
+/// `@group(0)`.
+pub struct CameraResources {
+    pub buffer: wgpu::Buffer,
+    pub bind_group_layout: wgpu::BindGroupLayout,
+    pub bind_group: wgpu::BindGroup,
+}
+
+impl CameraResources {
+    pub fn new(device: &wgpu::Device, uniform: CameraUniform) -> Self {
+        let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Camera Buffer"),
+            contents: bytemuck::cast_slice(&[uniform]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Camera Bind Group Layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Camera Bind Group"),
+            layout: &bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: buffer.as_entire_binding(),
+            }],
+        });
+
+        Self {
+            buffer,
+            bind_group_layout,
+            bind_group,
+        }
+    }
+
+    pub fn write(&self, queue: &wgpu::Queue, uniform: CameraUniform) {
+        queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(&[uniform]));
+    }
+}
+
+/// Tracks mouse-drag and keyboard state and turns input events into changes
+/// to a [`Camera`].
+#[derive(Default)]
+pub struct CameraController {
+    dragging: bool,
+    last_cursor: Option<(f64, f64)>,
+}
+
+impl CameraController {
+    const PAN_KEY_STEP: f32 = 0.05;
+    const ZOOM_STEP: f32 = 0.1;
+    const MIN_ZOOM: f32 = 0.05;
+    const MAX_ZOOM: f32 = 20.0;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_dragging(&mut self, dragging: bool) {
+        self.dragging = dragging;
+    }
+
+    /// `cursor` is the cursor position in clip space (NDC). Returns whether
+    /// the camera changed and a redraw should be requested.
+    pub fn process_cursor_moved(&mut self, camera: &mut Camera, cursor: (f64, f64)) -> bool {
+        let moved = if self.dragging {
+            if let Some(last) = self.last_cursor {
+                let dx = (cursor.0 - last.0) as f32;
+                let dy = (cursor.1 - last.1) as f32;
+                let (sx, sy) = camera.axis_scale();
+                camera.pan[0] -= dx / (camera.zoom * sx);
+                camera.pan[1] -= dy / (camera.zoom * sy);
+                true
+            } else {
+                false
+            }
+        } else {
+            false
+        };
+        self.last_cursor = Some(cursor);
+        moved
+    }
+
+    /// `cursor_clip` is the cursor position in clip space at the time of the
+    /// scroll; the world point beneath it stays fixed across the zoom.
+    pub fn process_scroll(&mut self, camera: &mut Camera, cursor_clip: [f32; 2], scroll: f32) {
+        let cursor_world = camera.clip_to_world(cursor_clip);
+        camera.zoom = (camera.zoom * (1.0 + scroll * Self::ZOOM_STEP))
+            .clamp(Self::MIN_ZOOM, Self::MAX_ZOOM);
+        let new_world = camera.clip_to_world(cursor_clip);
+        camera.pan[0] += cursor_world[0] - new_world[0];
+        camera.pan[1] += cursor_world[1] - new_world[1];
+    }
+
+    pub fn pan_left(&self, camera: &mut Camera) {
+        camera.pan[0] -= Self::PAN_KEY_STEP / camera.zoom;
+    }
+    pub fn pan_right(&self, camera: &mut Camera) {
+        camera.pan[0] += Self::PAN_KEY_STEP / camera.zoom;
+    }
+    pub fn pan_up(&self, camera: &mut Camera) {
+        camera.pan[1] += Self::PAN_KEY_STEP / camera.zoom;
+    }
+    pub fn pan_down(&self, camera: &mut Camera) {
+        camera.pan[1] -= Self::PAN_KEY_STEP / camera.zoom;
+    }
+}
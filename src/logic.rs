@@ -1,8 +1,31 @@
+use lyon::{
+    path::Path,
+    tessellation::{
+        BuffersBuilder, LineCap, LineJoin, StrokeOptions, StrokeTessellator, StrokeVertex,
+        StrokeVertexConstructor, VertexBuffers,
+    },
+};
 use rand::{
     distributions::{Distribution, Standard},
     Rng,
 };
 
+use crate::Vertex;
+
+/// Turns the points lyon generates while stroking a path into our [`Vertex`]
+/// type, tagging every vertex it produces with a fixed colour.
+struct ColorVertexCtor([f32; 4]);
+
+impl StrokeVertexConstructor<Vertex> for ColorVertexCtor {
+    fn new_vertex(&mut self, vertex: StrokeVertex) -> Vertex {
+        let p = vertex.position();
+        Vertex {
+            pos: [p.x, p.y],
+            color: self.0,
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum Dir {
     Up,
@@ -60,7 +83,24 @@ impl Maze {
         }
     }
 
-    pub fn step(&mut self, rng: &mut impl Rng) {
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn origin(&self) -> (u32, u32) {
+        self.origin
+    }
+
+    /// Performs one origin-shift: the origin moves to a random neighbour and
+    /// the walls along the way are rewired to keep `tiles` a spanning tree.
+    /// Returns the origin's position *before* the shift, so callers can
+    /// highlight the wall that was just toggled (the edge between the
+    /// returned cell and the new [`Maze::origin`]).
+    pub fn step(&mut self, rng: &mut impl Rng) -> (u32, u32) {
         let mut new_origin;
         let mut direction;
         loop {
@@ -127,6 +167,8 @@ impl Maze {
         self.tiles[(new_origin.0 + new_origin.1 * self.width) as usize] = None;
 
         self.origin = new_origin;
+
+        old_origin
     }
 
     pub fn init(&mut self, rng: &mut impl Rng) {
@@ -135,6 +177,276 @@ impl Maze {
         }
     }
 
+    /// Colour used for every wall segment emitted by [`Maze::wall_geometry`].
+    const WALL_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+
+    /// Enumerates every present wall (the four borders plus interior
+    /// `horz_walls`/`vert_walls`) as a line segment in grid space, i.e. cell
+    /// `(x, y)` spans `[x, x+1] x [y, y+1]`. Shared by every wall renderer
+    /// (GPU tessellation, SVG export, ...) so they all agree on what a "wall"
+    /// is; each caller is responsible for mapping grid space into its own
+    /// coordinate system.
+    fn wall_segments(&self) -> Vec<(f32, f32, f32, f32)> {
+        let (w, h) = (self.width as f32, self.height as f32);
+        let mut segments = Vec::new();
+
+        // Border walls.
+        for x in 0..self.width {
+            let x = x as f32;
+            segments.push((x, 0.0, x + 1.0, 0.0));
+            segments.push((x, h, x + 1.0, h));
+        }
+        for y in 0..self.height {
+            let y = y as f32;
+            segments.push((0.0, y, 0.0, y + 1.0));
+            segments.push((w, y, w, y + 1.0));
+        }
+
+        // Horizontal interior walls, one row of `width` entries per gap between rows.
+        for y in 0..(self.height - 1) {
+            for x in 0..self.width {
+                if self.horz_walls[(x + y * self.width) as usize] {
+                    let (x, y) = (x as f32, (y + 1) as f32);
+                    segments.push((x, y, x + 1.0, y));
+                }
+            }
+        }
+
+        // Vertical interior walls, one column of `height` entries per gap between columns.
+        for y in 0..self.height {
+            for x in 0..(self.width - 1) {
+                if self.vert_walls[(x + y * (self.width - 1)) as usize] {
+                    let (x, y) = ((x + 1) as f32, y as f32);
+                    segments.push((x, y, x, y + 1.0));
+                }
+            }
+        }
+
+        segments
+    }
+
+    /// Maps a grid-space point (`0..=width`, `0..=height`) to clip space
+    /// `[-1, 1]`, scaling the shorter axis down so that cells stay square
+    /// regardless of the maze's aspect ratio. Shared by every geometry
+    /// method so they all agree on where a grid coordinate lands; see
+    /// [`Maze::cell_at`] for the inverse. This only corrects for the maze's
+    /// own aspect ratio — compensating for a non-square *window* is the
+    /// camera's job (`Camera::aspect` in `camera.rs`).
+    fn to_ndc(&self, x: f32, y: f32) -> lyon::math::Point {
+        let (w, h) = (self.width as f32, self.height as f32);
+        let (scale_x, scale_y) = if w > h { (1.0, w / h) } else { (h / w, 1.0) };
+        let nx = (x / w * 2.0 - 1.0) / scale_x;
+        let ny = (1.0 - y / h * 2.0) / scale_y;
+        lyon::math::point(nx, ny)
+    }
+
+    /// Inverse of [`Maze::to_ndc`]: maps a point in the same clip space back
+    /// to the grid cell containing it, or `None` if it falls outside the
+    /// maze. Used to turn a cursor position into a cell for the solver.
+    pub fn cell_at(&self, clip: [f32; 2]) -> Option<(u32, u32)> {
+        let (w, h) = (self.width as f32, self.height as f32);
+        let (scale_x, scale_y) = if w > h { (1.0, w / h) } else { (h / w, 1.0) };
+        let x = w * (clip[0] * scale_x + 1.0) / 2.0;
+        let y = h * (1.0 - clip[1] * scale_y) / 2.0;
+        if x < 0.0 || y < 0.0 {
+            return None;
+        }
+        let (cx, cy) = (x.floor() as u32, y.floor() as u32);
+        if cx < self.width && cy < self.height {
+            Some((cx, cy))
+        } else {
+            None
+        }
+    }
+
+    /// Tessellates every wall into a stroked triangle mesh `thickness` clip-space
+    /// units wide, ready to upload as a vertex + index buffer and draw with
+    /// `TriangleList`. `join`/`cap` control how corners and open ends are drawn.
+    pub fn wall_geometry(
+        &self,
+        thickness: f32,
+        join: LineJoin,
+        cap: LineCap,
+    ) -> VertexBuffers<Vertex, u32> {
+        let mut builder = Path::builder();
+        for (x0, y0, x1, y1) in self.wall_segments() {
+            builder.begin(self.to_ndc(x0, y0));
+            builder.line_to(self.to_ndc(x1, y1));
+            builder.end(false);
+        }
+        let path = builder.build();
+
+        let mut geometry: VertexBuffers<Vertex, u32> = VertexBuffers::new();
+        let options = StrokeOptions::default()
+            .with_line_width(thickness)
+            .with_line_join(join)
+            .with_line_cap(cap);
+        StrokeTessellator::new()
+            .tessellate_path(
+                &path,
+                &options,
+                &mut BuffersBuilder::new(&mut geometry, ColorVertexCtor(Self::WALL_COLOR)),
+            )
+            .expect("wall stroke tessellation failed");
+
+        geometry
+    }
+
+    /// Colour of the outline drawn around the cell the origin currently sits in.
+    const ORIGIN_COLOR: [f32; 4] = [1.0, 0.2, 0.2, 1.0];
+    /// Colour of the wall that the most recent [`Maze::step`] just toggled.
+    const TOGGLED_WALL_COLOR: [f32; 4] = [1.0, 0.9, 0.2, 1.0];
+
+    /// Tessellates two pieces of overlay geometry for the step-by-step
+    /// animation: an outline around the current origin cell, and the wall
+    /// segment toggled by the step that moved the origin here from
+    /// `previous_origin`. Meant to be drawn on top of [`Maze::wall_geometry`].
+    /// `join`/`cap` control how corners and open ends are drawn.
+    pub fn highlight_geometry(
+        &self,
+        previous_origin: (u32, u32),
+        thickness: f32,
+        join: LineJoin,
+        cap: LineCap,
+    ) -> VertexBuffers<Vertex, u32> {
+        let mut geometry: VertexBuffers<Vertex, u32> = VertexBuffers::new();
+        let options = StrokeOptions::default()
+            .with_line_width(thickness)
+            .with_line_join(join)
+            .with_line_cap(cap);
+        let mut tessellator = StrokeTessellator::new();
+
+        let (ox, oy) = (self.origin.0 as f32, self.origin.1 as f32);
+        let mut origin_path = Path::builder();
+        origin_path.begin(self.to_ndc(ox, oy));
+        origin_path.line_to(self.to_ndc(ox + 1.0, oy));
+        origin_path.line_to(self.to_ndc(ox + 1.0, oy + 1.0));
+        origin_path.line_to(self.to_ndc(ox, oy + 1.0));
+        origin_path.close();
+        tessellator
+            .tessellate_path(
+                &origin_path.build(),
+                &options,
+                &mut BuffersBuilder::new(&mut geometry, ColorVertexCtor(Self::ORIGIN_COLOR)),
+            )
+            .expect("origin highlight tessellation failed");
+
+        // The edge shared by `previous_origin` and the current origin, i.e.
+        // the wall `step` just toggled to make room for the move.
+        let (px, py) = (previous_origin.0 as f32, previous_origin.1 as f32);
+        let (x0, y0, x1, y1) = if px == ox {
+            let y = py.min(oy) + 1.0;
+            (px, y, px + 1.0, y)
+        } else {
+            let x = px.min(ox) + 1.0;
+            (x, py, x, py + 1.0)
+        };
+        let mut wall_path = Path::builder();
+        wall_path.begin(self.to_ndc(x0, y0));
+        wall_path.line_to(self.to_ndc(x1, y1));
+        wall_path.end(false);
+        tessellator
+            .tessellate_path(
+                &wall_path.build(),
+                &options,
+                &mut BuffersBuilder::new(&mut geometry, ColorVertexCtor(Self::TOGGLED_WALL_COLOR)),
+            )
+            .expect("toggled-wall highlight tessellation failed");
+
+        geometry
+    }
+
+    /// Follows the arrow chain from `cell` to `origin`, inclusive of both
+    /// ends, in that order.
+    fn chain_to_origin(&self, mut cell: (u32, u32)) -> Vec<(u32, u32)> {
+        let mut chain = vec![cell];
+        while cell != self.origin {
+            let dir = self.tiles[(cell.0 + cell.1 * self.width) as usize]
+                .as_ref()
+                .unwrap();
+            cell = match dir {
+                Dir::Up => (cell.0, cell.1 - 1),
+                Dir::Down => (cell.0, cell.1 + 1),
+                Dir::Left => (cell.0 - 1, cell.1),
+                Dir::Right => (cell.0 + 1, cell.1),
+            };
+            chain.push(cell);
+        }
+        chain
+    }
+
+    /// Finds the unique path between `from` and `to`, exploiting the fact
+    /// that `tiles` forms a spanning tree rooted at `origin`: following
+    /// arrows from any cell always reaches `origin`, so the path between two
+    /// cells is `from -> LCA -> to`, where the LCA is found by walking both
+    /// arrow chains from the `origin` end until they diverge.
+    pub fn solve(&self, from: (u32, u32), to: (u32, u32)) -> Vec<(u32, u32)> {
+        if from == to {
+            return vec![from];
+        }
+
+        // Both chains end at `origin`, so reversing them lines the two
+        // chains up from the root; they're guaranteed to share at least
+        // that first cell.
+        let from_chain = self.chain_to_origin(from);
+        let to_chain = self.chain_to_origin(to);
+        let from_rev: Vec<_> = from_chain.iter().rev().copied().collect();
+        let to_rev: Vec<_> = to_chain.iter().rev().copied().collect();
+
+        let mut shared = 0;
+        while shared < from_rev.len() && shared < to_rev.len() && from_rev[shared] == to_rev[shared]
+        {
+            shared += 1;
+        }
+        let lca = shared - 1;
+
+        let mut path: Vec<(u32, u32)> = from_rev[lca..].iter().rev().copied().collect();
+        path.extend(to_rev[(lca + 1)..].iter().copied());
+        path
+    }
+
+    /// Colour of the highlighted solve path between two cells.
+    const SOLVE_COLOR: [f32; 4] = [0.2, 0.6, 1.0, 1.0];
+
+    /// Tessellates `path` (as returned by [`Maze::solve`]) into a stroked
+    /// polyline through each cell's center, ready to draw on top of
+    /// [`Maze::wall_geometry`]. Returns empty geometry for paths shorter
+    /// than two cells. `join`/`cap` control how corners and open ends are drawn.
+    pub fn solve_geometry(
+        &self,
+        path: &[(u32, u32)],
+        thickness: f32,
+        join: LineJoin,
+        cap: LineCap,
+    ) -> VertexBuffers<Vertex, u32> {
+        let mut geometry: VertexBuffers<Vertex, u32> = VertexBuffers::new();
+        if path.len() < 2 {
+            return geometry;
+        }
+
+        let mut builder = Path::builder();
+        let (x0, y0) = path[0];
+        builder.begin(self.to_ndc(x0 as f32 + 0.5, y0 as f32 + 0.5));
+        for &(x, y) in &path[1..] {
+            builder.line_to(self.to_ndc(x as f32 + 0.5, y as f32 + 0.5));
+        }
+        builder.end(false);
+
+        let options = StrokeOptions::default()
+            .with_line_width(thickness)
+            .with_line_join(join)
+            .with_line_cap(cap);
+        StrokeTessellator::new()
+            .tessellate_path(
+                &builder.build(),
+                &options,
+                &mut BuffersBuilder::new(&mut geometry, ColorVertexCtor(Self::SOLVE_COLOR)),
+            )
+            .expect("solve path tessellation failed");
+
+        geometry
+    }
+
     pub fn to_str(&self, dirs: bool) -> String {
         let mut result = String::new();
 
@@ -183,4 +495,115 @@ impl Maze {
 
         result
     }
+
+    /// Renders every wall as an SVG document `cell_size` pixels per grid
+    /// unit, with `stroke` the line width in pixels. Reuses [`Maze::wall_segments`]
+    /// so the vector export and the GPU tessellation in [`Maze::wall_geometry`]
+    /// always agree on what a "wall" is.
+    pub fn to_svg(&self, cell_size: f32, stroke: f32) -> String {
+        use std::fmt::Write;
+
+        let width = self.width as f32 * cell_size;
+        let height = self.height as f32 * cell_size;
+
+        let mut svg = String::new();
+        write!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#,
+        )
+        .unwrap();
+        write!(
+            svg,
+            r#"<rect width="{width}" height="{height}" fill="white"/><path stroke="black" stroke-width="{stroke}" stroke-linecap="square" fill="none" d=""#,
+        )
+        .unwrap();
+        for (x0, y0, x1, y1) in self.wall_segments() {
+            write!(
+                svg,
+                "M{} {}L{} {}",
+                x0 * cell_size,
+                y0 * cell_size,
+                x1 * cell_size,
+                y1 * cell_size,
+            )
+            .unwrap();
+        }
+        svg += r#""/></svg>"#;
+
+        svg
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solve_from_equals_to_is_a_single_cell_path() {
+        let maze = Maze::new(4, 3);
+        assert_eq!(maze.solve((2, 1), (2, 1)), vec![(2, 1)]);
+    }
+
+    #[test]
+    fn solve_from_origin_walks_straight_to_the_target() {
+        // With no `step`s run, `new`'s default spanning tree points every
+        // cell `Left` towards column 0, and column 0 `Up` towards the
+        // origin, so the chain from origin to (2, 1) is fully deterministic.
+        let maze = Maze::new(4, 3);
+        assert_eq!(
+            maze.solve((0, 0), (2, 1)),
+            vec![(0, 0), (0, 1), (1, 1), (2, 1)]
+        );
+    }
+
+    #[test]
+    fn solve_to_origin_is_the_reverse_chain() {
+        let maze = Maze::new(4, 3);
+        assert_eq!(
+            maze.solve((2, 1), (0, 0)),
+            vec![(2, 1), (1, 1), (0, 1), (0, 0)]
+        );
+    }
+
+    #[test]
+    fn solve_between_two_non_origin_cells_splices_through_the_lca() {
+        let maze = Maze::new(4, 3);
+        // Chain to origin: (2,1) -> (1,1) -> (0,1) -> (0,0).
+        // Chain to origin: (3,2) -> (2,2) -> (1,2) -> (0,2) -> (0,1) -> (0,0).
+        // Shared suffix (from the origin end) is (0,0), (0,1), so the LCA is
+        // (0,1) and the path splices `(2,1) -> (0,1)` with `(0,1) -> (3,2)`.
+        let maze_chain = maze.solve((2, 1), (3, 2));
+        assert_eq!(
+            maze_chain,
+            vec![(2, 1), (1, 1), (0, 1), (0, 2), (1, 2), (2, 2), (3, 2)]
+        );
+    }
+
+    #[test]
+    fn to_ndc_and_cell_at_round_trip_every_cell() {
+        let maze = Maze::new(5, 4);
+        for y in 0..maze.height() {
+            for x in 0..maze.width() {
+                let center = maze.to_ndc(x as f32 + 0.5, y as f32 + 0.5);
+                assert_eq!(maze.cell_at([center.x, center.y]), Some((x, y)));
+            }
+        }
+    }
+
+    #[test]
+    fn cell_at_rejects_points_outside_the_maze() {
+        let maze = Maze::new(5, 4);
+        assert_eq!(maze.cell_at([-10.0, -10.0]), None);
+        assert_eq!(maze.cell_at([10.0, 10.0]), None);
+    }
+
+    #[test]
+    fn to_svg_is_well_formed_and_sized_from_the_grid() {
+        let maze = Maze::new(3, 2);
+        let svg = maze.to_svg(10.0, 1.0);
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.ends_with("</svg>"));
+        assert!(svg.contains(r#"width="30""#));
+        assert!(svg.contains(r#"height="20""#));
+    }
 }
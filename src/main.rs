@@ -1,34 +1,38 @@
-use std::{borrow::Cow, iter};
+use std::{borrow::Cow, iter, sync::Arc};
 
 use bytemuck::{Pod, Zeroable};
+use lyon::tessellation::{LineCap, LineJoin};
 use wgpu::util::DeviceExt;
 use winit::{
-    event::{Event, WindowEvent},
+    event::{ElementState, Event, MouseButton, MouseScrollDelta, WindowEvent},
     event_loop::EventLoop,
+    keyboard::{Key, NamedKey},
     window::Window,
 };
 
+mod camera;
 mod logic;
+mod render_target;
+
+use render_target::{RenderTarget, SwapChainTarget, TextureTarget};
 
 #[repr(C)]
 #[derive(Clone, Copy, Pod, Zeroable)]
-struct Vertex {
-    _pos: [f32; 2],
-    _color: [f32; 4],
+pub(crate) struct Vertex {
+    pub(crate) pos: [f32; 2],
+    pub(crate) color: [f32; 4],
 }
 
-fn create_bundle(
+fn create_pipeline(
     device: &wgpu::Device,
     swapchain_format: wgpu::TextureFormat,
     shader: &wgpu::ShaderModule,
     pipeline_layout: &wgpu::PipelineLayout,
     sample_count: u32,
-    vertex_buffer: &wgpu::Buffer,
-    vertex_count: u32,
-) -> wgpu::RenderBundle {
+) -> wgpu::RenderPipeline {
     log::info!("sample_count: {}", sample_count);
 
-    let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
         label: None,
         layout: Some(pipeline_layout),
         vertex: wgpu::VertexState {
@@ -48,7 +52,7 @@ fn create_bundle(
             targets: &[Some(swapchain_format.into())],
         }),
         primitive: wgpu::PrimitiveState {
-            topology: wgpu::PrimitiveTopology::LineList,
+            topology: wgpu::PrimitiveTopology::TriangleList,
             front_face: wgpu::FrontFace::Ccw,
             ..Default::default()
         },
@@ -59,7 +63,26 @@ fn create_bundle(
         },
         multiview: None,
         cache: None,
-    });
+    })
+}
+
+/// Builds a one-shot [`wgpu::RenderBundle`] drawing `index_count` indices out
+/// of `vertex_buffer`/`index_buffer`. Used by the headless PNG export, which
+/// renders a single static frame; the interactive, animated path in [`run`]
+/// instead draws directly against a buffer that is re-tessellated on every
+/// animation step or solver click.
+fn create_bundle(
+    device: &wgpu::Device,
+    swapchain_format: wgpu::TextureFormat,
+    shader: &wgpu::ShaderModule,
+    pipeline_layout: &wgpu::PipelineLayout,
+    sample_count: u32,
+    vertex_buffer: &wgpu::Buffer,
+    index_buffer: &wgpu::Buffer,
+    index_count: u32,
+    camera_bind_group: &wgpu::BindGroup,
+) -> wgpu::RenderBundle {
+    let pipeline = create_pipeline(device, swapchain_format, shader, pipeline_layout, sample_count);
 
     let mut encoder = device.create_render_bundle_encoder(&wgpu::RenderBundleEncoderDescriptor {
         label: None,
@@ -69,13 +92,70 @@ fn create_bundle(
         multiview: None,
     });
     encoder.set_pipeline(&pipeline);
+    encoder.set_bind_group(0, camera_bind_group, &[]);
     encoder.set_vertex_buffer(0, vertex_buffer.slice(..));
-    encoder.draw(0..vertex_count, 0..1);
+    encoder.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+    encoder.draw_indexed(0..index_count, 0, 0..1);
     encoder.finish(&wgpu::RenderBundleDescriptor {
         label: Some("main"),
     })
 }
 
+/// Stroke width of maze walls, in clip-space units (the maze spans `[-1, 1]`
+/// along its longer axis).
+const WALL_THICKNESS: f32 = 0.01;
+const WALL_LINE_JOIN: LineJoin = LineJoin::Miter;
+const WALL_LINE_CAP: LineCap = LineCap::Square;
+
+const HIGHLIGHT_LINE_JOIN: LineJoin = LineJoin::Miter;
+const HIGHLIGHT_LINE_CAP: LineCap = LineCap::Square;
+
+const SOLVE_LINE_JOIN: LineJoin = LineJoin::Round;
+const SOLVE_LINE_CAP: LineCap = LineCap::Round;
+
+/// Re-tessellates `maze`'s walls plus the origin/toggled-wall highlight and,
+/// if both `solve_endpoints` are set, the highlighted path between them;
+/// uploads the combined geometry into `vertex_buffer`/`index_buffer` and
+/// returns the number of indices to draw.
+fn upload_maze_geometry(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    maze: &logic::Maze,
+    previous_origin: (u32, u32),
+    solve_endpoints: (Option<(u32, u32)>, Option<(u32, u32)>),
+    vertex_buffer: &mut render_target::DynamicBuffer,
+    index_buffer: &mut render_target::DynamicBuffer,
+) -> u32 {
+    let mut geometry = maze.wall_geometry(WALL_THICKNESS, WALL_LINE_JOIN, WALL_LINE_CAP);
+
+    let mut append = |extra: lyon::tessellation::VertexBuffers<Vertex, u32>| {
+        let offset = geometry.vertices.len() as u32;
+        geometry.vertices.extend(extra.vertices);
+        geometry
+            .indices
+            .extend(extra.indices.into_iter().map(|i| i + offset));
+    };
+
+    append(maze.highlight_geometry(
+        previous_origin,
+        WALL_THICKNESS * 1.5,
+        HIGHLIGHT_LINE_JOIN,
+        HIGHLIGHT_LINE_CAP,
+    ));
+    if let (Some(from), Some(to)) = solve_endpoints {
+        append(maze.solve_geometry(
+            &maze.solve(from, to),
+            WALL_THICKNESS * 1.25,
+            SOLVE_LINE_JOIN,
+            SOLVE_LINE_CAP,
+        ));
+    }
+
+    vertex_buffer.write(device, queue, bytemuck::cast_slice(&geometry.vertices));
+    index_buffer.write(device, queue, bytemuck::cast_slice(&geometry.indices));
+    geometry.indices.len() as u32
+}
+
 fn create_multisampled_framebuffer(
     device: &wgpu::Device,
     config: &wgpu::SurfaceConfiguration,
@@ -103,14 +183,20 @@ fn create_multisampled_framebuffer(
         .create_view(&wgpu::TextureViewDescriptor::default())
 }
 
-async fn run(event_loop: EventLoop<()>, window: Window) {
+async fn run(event_loop: EventLoop<()>, window: Window, maze: logic::Maze) {
+    // `Surface<'static>` (the type `SwapChainTarget` stores) needs a window
+    // handle that outlives the surface itself; an `Arc` lets us hand wgpu an
+    // owned, cloneable handle instead of a borrow tied to this function's
+    // stack frame.
+    let window = Arc::new(window);
+
     let mut size = window.inner_size();
     size.width = size.width.max(1);
     size.height = size.height.max(1);
 
     let instance = wgpu::Instance::default();
 
-    let surface = instance.create_surface(&window).unwrap();
+    let surface = instance.create_surface(window.clone()).unwrap();
     let adapter = instance
         .request_adapter(&wgpu::RequestAdapterOptions {
             power_preference: wgpu::PowerPreference::default(),
@@ -138,9 +224,16 @@ async fn run(event_loop: EventLoop<()>, window: Window) {
         source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(include_str!("shader.wgsl"))),
     });
 
+    let mut camera = camera::Camera {
+        aspect: size.width as f32 / size.height as f32,
+        ..camera::Camera::default()
+    };
+    let mut camera_controller = camera::CameraController::new();
+    let camera_resources = camera::CameraResources::new(&device, camera::CameraUniform::new(&camera));
+
     let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
         label: None,
-        bind_group_layouts: &[],
+        bind_group_layouts: &[&camera_resources.bind_group_layout],
         push_constant_ranges: &[],
     });
 
@@ -148,10 +241,11 @@ async fn run(event_loop: EventLoop<()>, window: Window) {
         .get_default_config(&adapter, size.width, size.height)
         .unwrap();
 
-    surface.configure(&device, &config);
-
     let swapchain_capabilities = surface.get_capabilities(&adapter);
     let swapchain_format = swapchain_capabilities.formats[0];
+    config.format = swapchain_format;
+
+    let mut target = SwapChainTarget::new(surface, &config, &device);
 
     let sample_flags = adapter.get_texture_format_features(swapchain_format).flags;
 
@@ -169,71 +263,165 @@ async fn run(event_loop: EventLoop<()>, window: Window) {
         }
     };
 
-    let mut vertex_data = vec![];
-    let max = 50;
-    for i in 0..max {
-        let percent = i as f32 / max as f32;
-        let (sin, cos) = (percent * 2.0 * std::f32::consts::PI).sin_cos();
-        vertex_data.push(Vertex {
-            _pos: [0.0, 0.0],
-            _color: [1.0, -sin, cos, 1.0],
-        });
-        vertex_data.push(Vertex {
-            _pos: [cos, sin],
-            _color: [sin, -cos, 1.0, 1.0],
-        });
-    }
+    let pipeline = create_pipeline(&device, swapchain_format, &shader, &pipeline_layout, sample_count);
 
-    let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-        label: Some("Vertex Buffer"),
-        contents: bytemuck::cast_slice(&vertex_data),
-        usage: wgpu::BufferUsages::VERTEX,
-    });
-    let vertex_count = vertex_data.len() as u32;
+    let mut vertex_buffer = render_target::DynamicBuffer::new(
+        &device,
+        "Vertex Buffer",
+        wgpu::BufferUsages::VERTEX,
+    );
+    let mut index_buffer =
+        render_target::DynamicBuffer::new(&device, "Index Buffer", wgpu::BufferUsages::INDEX);
 
-    let bundle = create_bundle(
+    let mut maze = maze;
+    let mut rng = rand::thread_rng();
+    let mut paused = false;
+    let mut single_step = false;
+    // The origin-shift algorithm has nothing to highlight until the first
+    // step runs, so prime it with one right away.
+    let mut previous_origin = maze.step(&mut rng);
+
+    // Endpoints for `Maze::solve`, picked by right-clicking two cells.
+    let mut solve_from: Option<(u32, u32)> = None;
+    let mut solve_to: Option<(u32, u32)> = None;
+
+    const STEP_INTERVAL: std::time::Duration = std::time::Duration::from_millis(80);
+    let mut next_step = std::time::Instant::now();
+
+    let mut index_count = upload_maze_geometry(
         &device,
-        swapchain_format,
-        &shader,
-        &pipeline_layout,
-        sample_count,
-        &vertex_buffer,
-        vertex_count,
+        &queue,
+        &maze,
+        previous_origin,
+        (solve_from, solve_to),
+        &mut vertex_buffer,
+        &mut index_buffer,
     );
 
-    let window = &window;
+    let mut cursor_clip = (0.0, 0.0);
+
     event_loop
-        .run(move |event, target| {
+        .run(move |event, elwt| {
             let _ = (&instance, &adapter, &shader, &pipeline_layout);
 
-            if let Event::WindowEvent {
-                window_id: _,
-                event,
-            } = event
-            {
-                match event {
+            match event {
+                Event::WindowEvent {
+                    window_id: _,
+                    event,
+                } => match event {
                     WindowEvent::Resized(new_size) => {
                         config.width = new_size.width.max(1);
                         config.height = new_size.height.max(1);
 
-                        surface.configure(&device, &config);
+                        target.resize(&device, &config);
+                        camera.aspect = config.width as f32 / config.height as f32;
+                        camera_resources.write(&queue, camera::CameraUniform::new(&camera));
+                        window.request_redraw();
+                    }
+                    WindowEvent::CloseRequested => elwt.exit(),
+                    WindowEvent::CursorMoved { position, .. } => {
+                        cursor_clip = (
+                            position.x / config.width as f64 * 2.0 - 1.0,
+                            1.0 - position.y / config.height as f64 * 2.0,
+                        );
+                        if camera_controller.process_cursor_moved(&mut camera, cursor_clip) {
+                            camera_resources.write(&queue, camera::CameraUniform::new(&camera));
+                            window.request_redraw();
+                        }
+                    }
+                    WindowEvent::MouseInput {
+                        state,
+                        button: MouseButton::Left,
+                        ..
+                    } => {
+                        camera_controller.set_dragging(state == ElementState::Pressed);
+                    }
+                    WindowEvent::MouseInput {
+                        state: ElementState::Pressed,
+                        button: MouseButton::Right,
+                        ..
+                    } => {
+                        let world = camera.clip_to_world([cursor_clip.0 as f32, cursor_clip.1 as f32]);
+                        if let Some(cell) = maze.cell_at(world) {
+                            // Right-click picks the solver's two endpoints in
+                            // turn: a third click starts a fresh pair rather
+                            // than extending the old one.
+                            if solve_from.is_none() || solve_to.is_some() {
+                                solve_from = Some(cell);
+                                solve_to = None;
+                            } else {
+                                solve_to = Some(cell);
+                            }
+                            index_count = upload_maze_geometry(
+                                &device,
+                                &queue,
+                                &maze,
+                                previous_origin,
+                                (solve_from, solve_to),
+                                &mut vertex_buffer,
+                                &mut index_buffer,
+                            );
+                            window.request_redraw();
+                        }
+                    }
+                    WindowEvent::MouseWheel { delta, .. } => {
+                        let scroll = match delta {
+                            MouseScrollDelta::LineDelta(_, y) => y,
+                            MouseScrollDelta::PixelDelta(pos) => (pos.y / 100.0) as f32,
+                        };
+                        camera_controller.process_scroll(
+                            &mut camera,
+                            [cursor_clip.0 as f32, cursor_clip.1 as f32],
+                            scroll,
+                        );
+                        camera_resources.write(&queue, camera::CameraUniform::new(&camera));
+                        window.request_redraw();
+                    }
+                    WindowEvent::KeyboardInput { event, .. }
+                        if event.state == ElementState::Pressed =>
+                    {
+                        match event.logical_key {
+                            Key::Named(NamedKey::ArrowLeft) => camera_controller.pan_left(&mut camera),
+                            Key::Named(NamedKey::ArrowRight) => {
+                                camera_controller.pan_right(&mut camera)
+                            }
+                            Key::Named(NamedKey::ArrowUp) => camera_controller.pan_up(&mut camera),
+                            Key::Named(NamedKey::ArrowDown) => {
+                                camera_controller.pan_down(&mut camera)
+                            }
+                            Key::Named(NamedKey::Space) => {
+                                paused = !paused;
+                                return;
+                            }
+                            Key::Character(ref s) if s == "n" => {
+                                single_step = true;
+                                return;
+                            }
+                            _ => return,
+                        }
+                        camera_resources.write(&queue, camera::CameraUniform::new(&camera));
                         window.request_redraw();
                     }
-                    WindowEvent::CloseRequested => target.exit(),
                     WindowEvent::RedrawRequested => {
-                        let frame = surface
-                            .get_current_texture()
+                        let frame = target
+                            .get_next_texture()
                             .expect("Failed to acquire next swap chain texture");
-                        let view = if sample_count == 1 {
-                            frame
-                                .texture
-                                .create_view(&wgpu::TextureViewDescriptor::default())
+                        let surface_view = target.view(&frame);
+                        // With MSAA, the render pass must resolve the
+                        // multisampled texture into the swapchain view itself
+                        // — otherwise the swapchain texture is presented
+                        // untouched and nothing ever reaches the screen.
+                        let (view, resolve_target) = if sample_count == 1 {
+                            (surface_view, None)
                         } else {
-                            create_multisampled_framebuffer(
-                                &device,
-                                &config,
-                                swapchain_format,
-                                sample_count,
+                            (
+                                create_multisampled_framebuffer(
+                                    &device,
+                                    &config,
+                                    swapchain_format,
+                                    sample_count,
+                                ),
+                                Some(&surface_view),
                             )
                         };
 
@@ -244,7 +432,7 @@ async fn run(event_loop: EventLoop<()>, window: Window) {
                         {
                             let rpass_color_attachment = wgpu::RenderPassColorAttachment {
                                 view: &view,
-                                resolve_target: None,
+                                resolve_target,
                                 ops: wgpu::Operations {
                                     load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
                                     store: if sample_count == 1 {
@@ -254,34 +442,187 @@ async fn run(event_loop: EventLoop<()>, window: Window) {
                                     },
                                 },
                             };
-                            encoder
-                                .begin_render_pass(&wgpu::RenderPassDescriptor {
-                                    label: None,
-                                    color_attachments: &[Some(rpass_color_attachment)],
-                                    depth_stencil_attachment: None,
-                                    timestamp_writes: None,
-                                    occlusion_query_set: None,
-                                })
-                                .execute_bundles(iter::once(&bundle));
+                            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                                label: None,
+                                color_attachments: &[Some(rpass_color_attachment)],
+                                depth_stencil_attachment: None,
+                                timestamp_writes: None,
+                                occlusion_query_set: None,
+                            });
+                            rpass.set_pipeline(&pipeline);
+                            rpass.set_bind_group(0, &camera_resources.bind_group, &[]);
+                            rpass.set_vertex_buffer(0, vertex_buffer.slice());
+                            rpass.set_index_buffer(index_buffer.slice(), wgpu::IndexFormat::Uint32);
+                            rpass.draw_indexed(0..index_count, 0, 0..1);
                         }
 
                         queue.submit(Some(encoder.finish()));
-                        frame.present();
+                        target.present(frame);
                     }
                     _ => {}
+                },
+                Event::AboutToWait => {
+                    let now = std::time::Instant::now();
+                    if single_step || (!paused && now >= next_step) {
+                        single_step = false;
+                        next_step = now + STEP_INTERVAL;
+                        previous_origin = maze.step(&mut rng);
+                        index_count = upload_maze_geometry(
+                            &device,
+                            &queue,
+                            &maze,
+                            previous_origin,
+                            (solve_from, solve_to),
+                            &mut vertex_buffer,
+                            &mut index_buffer,
+                        );
+                        window.request_redraw();
+                    }
+                    elwt.set_control_flow(winit::event_loop::ControlFlow::WaitUntil(next_step));
                 }
+                _ => {}
             }
         })
         .unwrap();
 }
 
+/// Renders a single frame of `maze` offscreen at `width`x`height` and saves it
+/// as a PNG at `output`. Used by the headless CLI path so the tool can be
+/// scripted without opening a window.
+async fn run_headless(maze: &logic::Maze, width: u32, height: u32, output: &std::path::Path) {
+    let instance = wgpu::Instance::default();
+
+    let adapter = instance
+        .request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::default(),
+            force_fallback_adapter: false,
+            compatible_surface: None,
+        })
+        .await
+        .expect("Failed to find an appropriate adapter");
+
+    let (device, queue) = adapter
+        .request_device(
+            &wgpu::DeviceDescriptor {
+                label: None,
+                required_features: wgpu::Features::empty(),
+                required_limits: wgpu::Limits::downlevel_webgl2_defaults(),
+                memory_hints: wgpu::MemoryHints::MemoryUsage,
+            },
+            None,
+        )
+        .await
+        .expect("Failed to create device");
+
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: None,
+        source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(include_str!("shader.wgsl"))),
+    });
+
+    let camera = camera::Camera {
+        aspect: width as f32 / height as f32,
+        ..camera::Camera::default()
+    };
+    let camera_resources = camera::CameraResources::new(&device, camera::CameraUniform::new(&camera));
+
+    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: None,
+        bind_group_layouts: &[&camera_resources.bind_group_layout],
+        push_constant_ranges: &[],
+    });
+
+    let mut target = TextureTarget::new(&device, wgpu::TextureFormat::Rgba8UnormSrgb, width, height);
+
+    let geometry = maze.wall_geometry(WALL_THICKNESS, WALL_LINE_JOIN, WALL_LINE_CAP);
+    let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some("Vertex Buffer"),
+        contents: bytemuck::cast_slice(&geometry.vertices),
+        usage: wgpu::BufferUsages::VERTEX,
+    });
+    let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some("Index Buffer"),
+        contents: bytemuck::cast_slice(&geometry.indices),
+        usage: wgpu::BufferUsages::INDEX,
+    });
+    let index_count = geometry.indices.len() as u32;
+
+    let bundle = create_bundle(
+        &device,
+        target.format(),
+        &shader,
+        &pipeline_layout,
+        1,
+        &vertex_buffer,
+        &index_buffer,
+        index_count,
+        &camera_resources.bind_group,
+    );
+
+    let frame = target
+        .get_next_texture()
+        .expect("Failed to acquire offscreen texture");
+    let view = target.view(&frame);
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+    {
+        let rpass_color_attachment = wgpu::RenderPassColorAttachment {
+            view: &view,
+            resolve_target: None,
+            ops: wgpu::Operations {
+                load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                store: wgpu::StoreOp::Store,
+            },
+        };
+        encoder
+            .begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: None,
+                color_attachments: &[Some(rpass_color_attachment)],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            })
+            .execute_bundles(iter::once(&bundle));
+    }
+    queue.submit(Some(encoder.finish()));
+    target.present(frame);
+
+    let image = target.capture(&device, &queue);
+    image.save(output).expect("Failed to write PNG");
+    log::info!("Wrote {}", output.display());
+}
+
 fn main() {
+    env_logger::init();
+
+    let args: Vec<String> = std::env::args().collect();
+    let mut rng = rand::thread_rng();
+
+    // `maze-gen --headless <width> <height> <output.png>` renders a single
+    // frame offscreen and exits, without opening a window.
+    if let [_, flag, width, height, output] = args.as_slice() {
+        if flag == "--headless" {
+            let mut maze = logic::Maze::new(40, 30);
+            maze.init(&mut rng);
+
+            let width: u32 = width.parse().expect("width must be a number");
+            let height: u32 = height.parse().expect("height must be a number");
+            let output = std::path::Path::new(output);
+
+            pollster::block_on(run_headless(&maze, width, height, output));
+            return;
+        }
+    }
+
+    // The windowed path leaves the maze uninitialized here: `run` drives
+    // `Maze::step` itself so the origin-shift animation is visible from the
+    // very first frame instead of jumping straight to a finished maze.
+    let maze = logic::Maze::new(40, 30);
+
     let event_loop = EventLoop::new().unwrap();
 
     let builder = winit::window::WindowBuilder::new();
 
     let window = builder.build(&event_loop).unwrap();
 
-    env_logger::init();
-    pollster::block_on(run(event_loop, window));
+    pollster::block_on(run(event_loop, window, maze));
 }
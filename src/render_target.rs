@@ -0,0 +1,259 @@
+//! Abstraction over "where a frame is rendered to", following the split used by
+//! ruffle's wgpu renderer: the same pipeline and render bundle can draw either
+//! straight to a window's surface or into an offscreen texture that gets read
+//! back to the CPU afterwards.
+
+/// Number of bytes a row of a mapped buffer must be aligned to, per wgpu's
+/// `COPY_BYTES_PER_ROW_ALIGNMENT`.
+const COPY_BYTES_PER_ROW_ALIGNMENT: u32 = 256;
+
+pub trait RenderTarget {
+    type Frame;
+
+    fn format(&self) -> wgpu::TextureFormat;
+    fn width(&self) -> u32;
+    fn height(&self) -> u32;
+
+    /// Acquire the next frame to render into.
+    fn get_next_texture(&mut self) -> Result<Self::Frame, wgpu::SurfaceError>;
+
+    /// View of a previously acquired frame, suitable for a render pass's
+    /// color attachment.
+    fn view(&self, frame: &Self::Frame) -> wgpu::TextureView;
+
+    /// Present or otherwise finalize a frame after the command buffer that
+    /// renders into it has been submitted.
+    fn present(&self, frame: Self::Frame);
+}
+
+/// Renders directly to a window's surface.
+pub struct SwapChainTarget {
+    surface: wgpu::Surface<'static>,
+    format: wgpu::TextureFormat,
+    width: u32,
+    height: u32,
+}
+
+impl SwapChainTarget {
+    pub fn new(
+        surface: wgpu::Surface<'static>,
+        config: &wgpu::SurfaceConfiguration,
+        device: &wgpu::Device,
+    ) -> Self {
+        surface.configure(device, config);
+        Self {
+            surface,
+            format: config.format,
+            width: config.width,
+            height: config.height,
+        }
+    }
+
+    pub fn resize(&mut self, device: &wgpu::Device, config: &wgpu::SurfaceConfiguration) {
+        self.width = config.width;
+        self.height = config.height;
+        self.surface.configure(device, config);
+    }
+}
+
+impl RenderTarget for SwapChainTarget {
+    type Frame = wgpu::SurfaceTexture;
+
+    fn format(&self) -> wgpu::TextureFormat {
+        self.format
+    }
+
+    fn width(&self) -> u32 {
+        self.width
+    }
+
+    fn height(&self) -> u32 {
+        self.height
+    }
+
+    fn get_next_texture(&mut self) -> Result<Self::Frame, wgpu::SurfaceError> {
+        self.surface.get_current_texture()
+    }
+
+    fn view(&self, frame: &Self::Frame) -> wgpu::TextureView {
+        frame
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default())
+    }
+
+    fn present(&self, frame: Self::Frame) {
+        frame.present();
+    }
+}
+
+/// Renders into an offscreen texture with `COPY_SRC` usage, so the result can
+/// be read back to the CPU and saved as an image.
+pub struct TextureTarget {
+    texture: wgpu::Texture,
+    format: wgpu::TextureFormat,
+    width: u32,
+    height: u32,
+    /// `bytes_per_row`, padded up to `COPY_BYTES_PER_ROW_ALIGNMENT`.
+    padded_bytes_per_row: u32,
+}
+
+impl TextureTarget {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat, width: u32, height: u32) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("offscreen render target"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+
+        let unpadded_bytes_per_row = width * 4;
+        let padding = (COPY_BYTES_PER_ROW_ALIGNMENT - unpadded_bytes_per_row % COPY_BYTES_PER_ROW_ALIGNMENT)
+            % COPY_BYTES_PER_ROW_ALIGNMENT;
+
+        Self {
+            texture,
+            format,
+            width,
+            height,
+            padded_bytes_per_row: unpadded_bytes_per_row + padding,
+        }
+    }
+
+    /// Copies the texture into a mapped buffer and decodes it into an RGBA
+    /// image. Must be called after the frame's command buffer has been
+    /// submitted; it polls the device itself until the copy is mapped.
+    pub fn capture(&self, device: &wgpu::Device, queue: &wgpu::Queue) -> image::RgbaImage {
+        let buffer_size = (self.padded_bytes_per_row * self.height) as wgpu::BufferAddress;
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("capture buffer"),
+            size: buffer_size,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("capture encoder"),
+        });
+        encoder.copy_texture_to_buffer(
+            self.texture.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(self.padded_bytes_per_row),
+                    rows_per_image: None,
+                },
+            },
+            wgpu::Extent3d {
+                width: self.width,
+                height: self.height,
+                depth_or_array_layers: 1,
+            },
+        );
+        queue.submit(Some(encoder.finish()));
+
+        let slice = buffer.slice(..);
+        let (tx, rx) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            tx.send(result).unwrap();
+        });
+        device.poll(wgpu::Maintain::Wait);
+        rx.recv().unwrap().expect("failed to map capture buffer");
+
+        let padded = slice.get_mapped_range();
+        let unpadded_bytes_per_row = (self.width * 4) as usize;
+        let mut pixels = Vec::with_capacity(unpadded_bytes_per_row * self.height as usize);
+        for row in padded.chunks(self.padded_bytes_per_row as usize) {
+            pixels.extend_from_slice(&row[..unpadded_bytes_per_row]);
+        }
+        drop(padded);
+        buffer.unmap();
+
+        image::RgbaImage::from_raw(self.width, self.height, pixels)
+            .expect("capture buffer has the wrong size for its own image dimensions")
+    }
+}
+
+/// A GPU buffer that grows to fit whatever is written to it, reusing the
+/// existing allocation via `queue.write_buffer` when the new contents still
+/// fit. Used for geometry that's re-tessellated over and over, such as the
+/// maze re-tessellated on every step of the animation, where recreating a
+/// buffer from scratch each time would be wasteful.
+pub struct DynamicBuffer {
+    buffer: wgpu::Buffer,
+    capacity: wgpu::BufferAddress,
+    usage: wgpu::BufferUsages,
+}
+
+impl DynamicBuffer {
+    pub fn new(device: &wgpu::Device, label: &str, usage: wgpu::BufferUsages) -> Self {
+        let capacity = 256;
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(label),
+            size: capacity,
+            usage: usage | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        Self {
+            buffer,
+            capacity,
+            usage,
+        }
+    }
+
+    /// Uploads `data`, growing the underlying buffer first if it's too small.
+    pub fn write(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, data: &[u8]) {
+        let len = data.len() as wgpu::BufferAddress;
+        if len > self.capacity {
+            self.capacity = len.next_power_of_two();
+            self.buffer = device.create_buffer(&wgpu::BufferDescriptor {
+                label: None,
+                size: self.capacity,
+                usage: self.usage | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+        }
+        queue.write_buffer(&self.buffer, 0, data);
+    }
+
+    /// The whole underlying buffer. Safe to bind in full even though it may
+    /// be larger than the last [`Self::write`]: draw calls bound it via an
+    /// explicit index/vertex count, so any leftover capacity just goes unused.
+    pub fn slice(&self) -> wgpu::BufferSlice {
+        self.buffer.slice(..)
+    }
+}
+
+impl RenderTarget for TextureTarget {
+    type Frame = ();
+
+    fn format(&self) -> wgpu::TextureFormat {
+        self.format
+    }
+
+    fn width(&self) -> u32 {
+        self.width
+    }
+
+    fn height(&self) -> u32 {
+        self.height
+    }
+
+    fn get_next_texture(&mut self) -> Result<Self::Frame, wgpu::SurfaceError> {
+        Ok(())
+    }
+
+    fn view(&self, _frame: &Self::Frame) -> wgpu::TextureView {
+        self.texture.create_view(&wgpu::TextureViewDescriptor::default())
+    }
+
+    fn present(&self, _frame: Self::Frame) {}
+}